@@ -0,0 +1,64 @@
+// Regression comparison against a prior run's results.json, so the harness
+// can gate CI on throughput/latency regressions instead of requiring someone
+// to eyeball the CSV.
+
+use crate::Metrics;
+
+struct MetricSpec {
+    label: &'static str,
+    higher_is_better: bool,
+    get: fn(&Metrics) -> f64,
+}
+
+const METRICS: &[MetricSpec] = &[
+    MetricSpec { label: "ops_per_sec", higher_is_better: true, get: |m| m.ops_per_sec },
+    MetricSpec { label: "latency_us_p50", higher_is_better: false, get: |m| m.latency_us_p50 },
+    MetricSpec { label: "latency_us_p95", higher_is_better: false, get: |m| m.latency_us_p95 },
+    MetricSpec { label: "latency_us_p99", higher_is_better: false, get: |m| m.latency_us_p99 },
+    MetricSpec { label: "reads_latency_us_p50", higher_is_better: false, get: |m| m.reads_latency_us_p50 },
+    MetricSpec { label: "reads_latency_us_p95", higher_is_better: false, get: |m| m.reads_latency_us_p95 },
+    MetricSpec { label: "reads_latency_us_p99", higher_is_better: false, get: |m| m.reads_latency_us_p99 },
+    MetricSpec { label: "writes_latency_us_p50", higher_is_better: false, get: |m| m.writes_latency_us_p50 },
+    MetricSpec { label: "writes_latency_us_p95", higher_is_better: false, get: |m| m.writes_latency_us_p95 },
+    MetricSpec { label: "writes_latency_us_p99", higher_is_better: false, get: |m| m.writes_latency_us_p99 },
+];
+
+// Prints a delta table comparing `current` against `baseline` and returns
+// `true` if any metric regressed by more than `fail_threshold_pct`.
+pub fn compare_and_report(baseline: &Metrics, current: &Metrics, fail_threshold_pct: f64) -> bool {
+    println!(
+        "{:<22} {:>14} {:>14} {:>10}",
+        "metric", "baseline", "current", "delta %"
+    );
+
+    let mut regressed = false;
+    for spec in METRICS {
+        let base_val = (spec.get)(baseline);
+        let cur_val = (spec.get)(current);
+        let pct_change = if base_val == 0.0 {
+            0.0
+        } else {
+            (cur_val - base_val) / base_val * 100.0
+        };
+
+        let is_regression = if spec.higher_is_better {
+            pct_change < -fail_threshold_pct
+        } else {
+            pct_change > fail_threshold_pct
+        };
+        if is_regression {
+            regressed = true;
+        }
+
+        println!(
+            "{:<22} {:>14.2} {:>14.2} {:>9.2}%{}",
+            spec.label,
+            base_val,
+            cur_val,
+            pct_change,
+            if is_regression { "  REGRESSION" } else { "" }
+        );
+    }
+
+    regressed
+}
@@ -1,13 +1,25 @@
+mod compare;
+mod ops_file;
+mod store;
+mod workload;
+
 use clap::Parser;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
+use std::io::Write;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Barrier, OnceLock};
 use std::time::{Duration, Instant};
 use std::thread;
 
+use ops_file::{OpRef, OpsFile};
+use store::{Backend, DashMapStore, MutexStore, ShardedRwLockStore, ArcSwapStore, Store};
+use workload::Dist;
+
+// Special value for `--ops` that switches to the in-memory Zipfian/uniform
+// workload generator instead of reading a text or binary ops log from disk.
+const GENERATE_OPS: &str = "generate";
+
 #[derive(Parser)]
 #[command(name = "bench-rust-core")]
 #[command(about = "Rust core benchmark for link shortener")]
@@ -23,21 +35,62 @@ struct Args {
     
     #[arg(long, default_value = "128")]
     shards: usize,
-    
+
+    #[arg(long, value_enum, default_value = "sharded-rwlock")]
+    backend: Backend,
+
     #[arg(long = "warmup_s", default_value = "2.0")]
     warmup_s: f64,
     
     #[arg(long = "duration_s", default_value = "10.0")]
     duration_s: f64,
-    
+
     #[arg(long, default_value = "results.json")]
     out: String,
-}
 
-#[derive(Debug, Clone)]
-enum Op {
-    Get(String),
-    Set(String, String),
+    // Open-loop mode: total intended ops/sec across all threads. Each op's
+    // start time is scheduled up front, so slow samples are still recorded
+    // (correcting for coordinated omission) instead of silently vanishing
+    // when the store stalls. Omit for the default closed-loop mode.
+    #[arg(long = "target_ops_per_sec")]
+    target_ops_per_sec: Option<f64>,
+
+    // CI-gating mode: compare this run's metrics against a previous
+    // results.json and exit non-zero on regression. Omit to just run and
+    // write results as normal.
+    #[arg(long)]
+    baseline: Option<String>,
+
+    #[arg(long = "fail_threshold_pct", default_value = "5.0")]
+    fail_threshold_pct: f64,
+
+    // Generated-workload knobs, used when `--ops generate` is in effect.
+    // `read_pct` in the recorded `Config` reflects the actual mix of the
+    // loaded ops file (computed from the file itself, not this flag) when
+    // ops come from disk instead of being generated; `dist` is only
+    // meaningful for generated workloads and is recorded as `"file"`
+    // otherwise.
+    #[arg(long = "read_pct", default_value = "95")]
+    read_pct: usize,
+
+    #[arg(long, value_enum, default_value = "uniform")]
+    dist: Dist,
+
+    #[arg(long, default_value = "0.99")]
+    theta: f64,
+
+    #[arg(long, default_value = "0")]
+    seed: u64,
+
+    // Number of ops to synthesize when `--ops generate` is used.
+    #[arg(long = "ops_count", default_value = "1000000")]
+    ops_count: usize,
+
+    // Ops are dealt to threads in fixed-size chunks, shuffled across the
+    // whole run rather than handed out as one contiguous slice per thread,
+    // so no single thread only ever touches one part of the key space.
+    #[arg(long = "chunk_size", default_value = "4096")]
+    chunk_size: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -66,9 +119,10 @@ struct Config {
     ops_file: String,
     read_pct: usize,
     dist: String,
+    backend: String,
     threads: usize,
     shards: usize,
-    seed: usize,
+    seed: u64,
     warmup_s: f64,
     duration_s: f64,
 }
@@ -94,141 +148,156 @@ struct Metrics {
     rss_bytes: Option<u64>,
 }
 
-// FNV-1a 64-bit hash
-fn fnv1a64(s: &str) -> u64 {
-    const FNV_OFFSET_BASIS: u64 = 14695981039346656037;
-    const FNV_PRIME: u64 = 1099511628211;
-    
-    let mut hash = FNV_OFFSET_BASIS;
-    for byte in s.bytes() {
-        hash ^= byte as u64;
-        hash = hash.wrapping_mul(FNV_PRIME);
-    }
-    hash
-}
+// HDR-style log-linear histogram of latencies in nanoseconds.
+//
+// Values below `2 * sub` (~2048ns at 3 significant digits) are stored
+// one-per-slot for exact resolution, since that's where the bulk of an
+// in-memory store's latencies land. Values at or above `2 * sub` are
+// bucketed into "magnitudes" (powers of two) with `sub` linear sub-buckets
+// per magnitude, giving constant *relative* error (~0.1% for 3 significant
+// digits) with no upper bound on the recorded value. The backing store is
+// a flat, lazily-grown `Vec<u64>` indexed by `flat_index`.
+const HIST_SIGNIFICANT_DIGITS: u32 = 3;
 
-// Histogram with fine-grained buckets for accurate latency measurement
-// Uses 1μs buckets up to 10ms (10,000 buckets), then coarser buckets up to 1s
 struct Histogram {
-    fine_buckets: Vec<u64>,  // 0-10ms in 1μs steps (10,000 buckets)
-    coarse_buckets: Vec<u64>, // 10ms-1s in 1ms steps (990 buckets)
+    buckets: Vec<u64>,
+    sub: u64,
+    sub_bits: u32,
     total: u64,
 }
 
 impl Histogram {
     fn new() -> Self {
+        let target = 10u64.pow(HIST_SIGNIFICANT_DIGITS);
+        let sub_bits = 64 - (target - 1).leading_zeros(); // ceil(log2(10^N))
+        let sub = 1u64 << sub_bits;
         Self {
-            fine_buckets: vec![0; 10000],  // 0-10ms at 1μs resolution
-            coarse_buckets: vec![0; 990], // 10ms-1s at 1ms resolution
+            buckets: vec![0; (2 * sub) as usize],
+            sub,
+            sub_bits,
             total: 0,
         }
     }
-    
-    fn record(&mut self, us: u64) {
-        if us < 10000 {
-            // Fine-grained: 1μs buckets
-            self.fine_buckets[us as usize] += 1;
-        } else if us < 1000000 {
-            // Coarse-grained: 1ms buckets (10ms to 1s)
-            let bucket = ((us - 10000) / 1000) as usize;
-            if bucket < self.coarse_buckets.len() {
-                self.coarse_buckets[bucket] += 1;
-            } else {
-                // Overflow: put in last bucket
-                *self.coarse_buckets.last_mut().unwrap() += 1;
-            }
+
+    // Position of the highest set bit (0 for v == 0), i.e. floor(log2(v)).
+    fn bit_length(v: u64) -> u32 {
+        if v == 0 {
+            0
         } else {
-            // > 1s: put in last bucket
-            *self.coarse_buckets.last_mut().unwrap() += 1;
+            63 - v.leading_zeros()
+        }
+    }
+
+    // Flat index into `buckets` for value `v`. Below `2 * sub`, the index
+    // is `v` itself (exact). At or above `2 * sub`, `bit_length(v)` is
+    // always at least `sub_bits + 1`, so `magnitude` is always >= 1 and
+    // `v >> magnitude` always lands in `[sub, 2 * sub)` — there's no
+    // below-`sub` case left to clamp away.
+    fn flat_index(&self, v: u64) -> usize {
+        if v < 2 * self.sub {
+            return v as usize;
+        }
+        let magnitude = Self::bit_length(v).saturating_sub(self.sub_bits) as u64;
+        let sub_idx = (v >> magnitude) - self.sub;
+        (self.sub * (magnitude + 1) + sub_idx) as usize
+    }
+
+    // Inverse of `flat_index`: the representative value for a flat bucket
+    // index.
+    fn bucket_value(&self, i: usize) -> u64 {
+        let i = i as u64;
+        if i < 2 * self.sub {
+            return i;
+        }
+        let magnitude = i / self.sub - 1;
+        let sub_idx = i % self.sub;
+        (self.sub + sub_idx) << magnitude
+    }
+
+    fn record(&mut self, v: u64) {
+        let idx = self.flat_index(v);
+        if self.buckets.len() <= idx {
+            self.buckets.resize(idx + 1, 0);
         }
+        self.buckets[idx] += 1;
         self.total += 1;
     }
-    
+
     fn merge(&mut self, other: &Self) {
-        for i in 0..self.fine_buckets.len() {
-            self.fine_buckets[i] += other.fine_buckets[i];
+        if self.buckets.len() < other.buckets.len() {
+            self.buckets.resize(other.buckets.len(), 0);
         }
-        for i in 0..self.coarse_buckets.len() {
-            self.coarse_buckets[i] += other.coarse_buckets[i];
+        for (slot, &count) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *slot += count;
         }
         self.total += other.total;
     }
-    
+
     fn percentile(&self, p: f64) -> f64 {
         if self.total == 0 {
             return 0.0;
         }
         let target = (self.total as f64 * p / 100.0).ceil() as u64;
         let mut count = 0u64;
-        
-        // Check fine-grained buckets first
-        for (i, &bucket_count) in self.fine_buckets.iter().enumerate() {
-            count += bucket_count;
-            if count >= target {
-                return i as f64; // Return exact microsecond value
-            }
-        }
-        
-        // Check coarse-grained buckets
-        for (i, &bucket_count) in self.coarse_buckets.iter().enumerate() {
+
+        for (i, &bucket_count) in self.buckets.iter().enumerate() {
             count += bucket_count;
             if count >= target {
-                // Return midpoint of 1ms bucket
-                return (10000 + i * 1000 + 500) as f64;
+                return self.bucket_value(i) as f64;
             }
         }
-        
-        // Fallback: return max (1s)
-        1000000.0
+
+        // Every recorded value landed somewhere above; this is unreachable
+        // as long as `total` matches the sum of `buckets`.
+        self.bucket_value(self.buckets.len() - 1) as f64
     }
 }
 
-type ShardedMap = Vec<Arc<std::sync::RwLock<HashMap<String, String>>>>;
+#[cfg(test)]
+mod histogram_tests {
+    use super::Histogram;
 
-fn load_initial(path: &str, shards: usize) -> ShardedMap {
-    let file = File::open(path).expect("Failed to open initial.tsv");
-    let reader = BufReader::new(file);
-    
-    let maps: Vec<Arc<std::sync::RwLock<HashMap<String, String>>>> = 
-        (0..shards).map(|_| Arc::new(std::sync::RwLock::new(HashMap::new()))).collect();
-    
-    for line in reader.lines() {
-        let line = line.expect("Failed to read line");
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 2 {
-            let code = parts[0].to_string();
-            let url = parts[1].to_string();
-            let shard_idx = (fnv1a64(&code) as usize) % shards;
-            maps[shard_idx].write().unwrap().insert(code, url);
+    #[test]
+    fn percentile_on_sub_microsecond_values_has_nanosecond_resolution() {
+        let mut hist = Histogram::new();
+        for v in 1..=1000u64 {
+            hist.record(v);
         }
+        assert_eq!(hist.percentile(50.0), 500.0);
     }
-    
-    maps
-}
 
-fn load_ops(path: &str) -> Vec<Op> {
-    let file = File::open(path).expect("Failed to open ops.txt");
-    let reader = BufReader::new(file);
-    let mut ops = Vec::new();
-    
-    for line in reader.lines() {
-        let line = line.expect("Failed to read line");
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.is_empty() {
-            continue;
-        }
-        match parts[0] {
-            "G" if parts.len() >= 2 => {
-                ops.push(Op::Get(parts[1].to_string()));
-            }
-            "S" if parts.len() >= 3 => {
-                ops.push(Op::Set(parts[1].to_string(), parts[2..].join(" ")));
-            }
-            _ => {}
-        }
+    #[test]
+    fn percentile_is_exact_below_the_linear_cutoff() {
+        let mut hist = Histogram::new();
+        hist.record(0);
+        hist.record(1);
+        hist.record(2047);
+        assert_eq!(hist.percentile(1.0), 0.0);
+        assert_eq!(hist.percentile(50.0), 1.0);
+        assert_eq!(hist.percentile(100.0), 2047.0);
+    }
+
+    #[test]
+    fn percentile_stays_within_relative_error_above_the_linear_cutoff() {
+        let mut hist = Histogram::new();
+        let v = 10_000_000u64;
+        hist.record(v);
+        let reported = hist.percentile(100.0);
+        let rel_error = (reported - v as f64).abs() / v as f64;
+        assert!(rel_error < 0.001, "relative error {rel_error} too large for {v}");
+    }
+
+    #[test]
+    fn merge_combines_totals_and_buckets_from_both_histograms() {
+        let mut a = Histogram::new();
+        let mut b = Histogram::new();
+        a.record(10);
+        b.record(20);
+        b.record(30);
+        a.merge(&b);
+        assert_eq!(a.total, 3);
+        assert_eq!(a.percentile(100.0), 30.0);
     }
-    
-    ops
 }
 
 struct WorkerResults {
@@ -236,91 +305,152 @@ struct WorkerResults {
     write_hist: Histogram,
     read_count: u64,
     write_count: u64,
-    total_ops: u64,
 }
 
-fn worker(
-    maps: Arc<ShardedMap>,
-    ops: Arc<Vec<Op>>,
-    start_idx: usize,
-    end_idx: usize,
+// Everything a worker thread needs that's shared across all threads, bundled
+// so `worker` doesn't take a dozen positional arguments.
+struct WorkerCtx<S: Store> {
+    store: Arc<S>,
+    ops: Arc<OpsFile>,
     warmup_duration: Duration,
     measure_duration: Duration,
     ops_counter: Arc<AtomicU64>,
-) -> WorkerResults {
+    open_loop_rate_per_sec: Option<f64>,
+    start_barrier: Barrier,
+    measure_start_once: OnceLock<Instant>,
+}
+
+// Walks a worker's assigned op-index chunks in dealt order, wrapping back to
+// the first chunk once the last is exhausted. Chunks are pre-shuffled and
+// round-robin dealt across threads (see `run_workers`) so a thread samples
+// across the whole ops file instead of one contiguous slice of it.
+struct ChunkCursor {
+    chunks: Vec<(usize, usize)>,
+    chunk_pos: usize,
+    op_idx: usize,
+}
+
+impl ChunkCursor {
+    fn new(chunks: Vec<(usize, usize)>) -> Self {
+        let op_idx = chunks[0].0;
+        Self { chunks, chunk_pos: 0, op_idx }
+    }
+
+    fn next_idx(&mut self) -> usize {
+        let idx = self.op_idx;
+        self.op_idx += 1;
+        if self.op_idx >= self.chunks[self.chunk_pos].1 {
+            self.chunk_pos = (self.chunk_pos + 1) % self.chunks.len();
+            self.op_idx = self.chunks[self.chunk_pos].0;
+        }
+        idx
+    }
+}
+
+// Executes one op against `store`, returning whether it was a read.
+fn apply_op<S: Store>(store: &S, op: OpRef<'_>) -> bool {
+    match op {
+        OpRef::Get(code) => {
+            let _ = store.get(code);
+            true
+        }
+        OpRef::Set(code, url) => {
+            store.set(code, url.to_string());
+            false
+        }
+    }
+}
+
+fn worker<S: Store>(ctx: Arc<WorkerCtx<S>>, chunks: Vec<(usize, usize)>) -> WorkerResults {
+    let store = &ctx.store;
+    let ops = &ctx.ops;
+    let warmup_duration = ctx.warmup_duration;
+    let measure_duration = ctx.measure_duration;
+    let ops_counter = &ctx.ops_counter;
+    let open_loop_rate_per_sec = ctx.open_loop_rate_per_sec;
     let mut read_hist = Histogram::new();
     let mut write_hist = Histogram::new();
     let mut local_reads = 0u64;
     let mut local_writes = 0u64;
     let mut local_ops = 0u64;
-    
+
     let warmup_end = Instant::now() + warmup_duration;
-    let measure_end = warmup_end + measure_duration;
-    
+
     // Warmup phase
-    let mut op_idx = start_idx;
+    let mut cursor = ChunkCursor::new(chunks.clone());
     while Instant::now() < warmup_end {
-        let op = &ops[op_idx];
-        match op {
-            Op::Get(code) => {
-                let shard_idx = (fnv1a64(code) as usize) % maps.len();
-                let _ = maps[shard_idx].read().unwrap().get(code);
-            }
-            Op::Set(code, url) => {
-                let shard_idx = (fnv1a64(code) as usize) % maps.len();
-                maps[shard_idx].write().unwrap().insert(code.clone(), url.clone());
-            }
-        }
-        op_idx += 1;
-        if op_idx >= end_idx {
-            op_idx = start_idx;
-        }
+        apply_op(store.as_ref(), ops.get(cursor.next_idx()));
     }
-    
+
+    // All threads rendezvous here once their own warmup is done, then agree
+    // on a single measure_start/measure_end so every thread measures the
+    // same wall-clock interval regardless of how staggered warmup was.
+    ctx.start_barrier.wait();
+    let measure_start = *ctx.measure_start_once.get_or_init(Instant::now);
+    let measure_end = measure_start + measure_duration;
+
     // Measurement phase
-    op_idx = start_idx;
-    while Instant::now() < measure_end {
-        let op = &ops[op_idx];
-        let start = Instant::now();
-        match op {
-            Op::Get(code) => {
-                let shard_idx = (fnv1a64(code) as usize) % maps.len();
-                let _ = maps[shard_idx].read().unwrap().get(code);
-            }
-            Op::Set(code, url) => {
-                let shard_idx = (fnv1a64(code) as usize) % maps.len();
-                maps[shard_idx].write().unwrap().insert(code.clone(), url.clone());
+    let mut cursor = ChunkCursor::new(chunks);
+    match open_loop_rate_per_sec {
+        None => {
+            // Closed loop: dispatch the next op only once the previous one
+            // returns. Latency is purely dispatch-to-completion.
+            while Instant::now() < measure_end {
+                let start = Instant::now();
+                let is_read = apply_op(store.as_ref(), ops.get(cursor.next_idx()));
+                let ns = start.elapsed().as_nanos() as u64;
+                if is_read {
+                    read_hist.record(ns);
+                    local_reads += 1;
+                } else {
+                    write_hist.record(ns);
+                    local_writes += 1;
+                }
+                local_ops += 1;
             }
         }
-        let elapsed = start.elapsed();
-        // Convert nanoseconds to microseconds with rounding
-        let ns = elapsed.as_nanos();
-        let us = (ns + 500) / 1000; // Round to nearest microsecond
-        match op {
-            Op::Get(_) => {
-                read_hist.record(us as u64);
-                local_reads += 1;
-            }
-            Op::Set(_, _) => {
-                write_hist.record(us as u64);
-                local_writes += 1;
+        Some(rate) => {
+            // Open loop: every op has an intended start time fixed up
+            // front. If we're running behind, the intended time is
+            // already in the past and the measured latency correctly
+            // absorbs the queueing delay instead of disappearing
+            // (coordinated omission).
+            //
+            // `rate` is validated to be finite and positive in `main`
+            // before any worker is spawned, so `1.0 / rate` is always a
+            // finite, positive number here.
+            let interval = Duration::from_secs_f64(1.0 / rate);
+            let mut i: u32 = 0;
+            let mut intended_start = measure_start;
+            while intended_start < measure_end {
+                let now = Instant::now();
+                if now < intended_start {
+                    thread::sleep(intended_start - now);
+                }
+
+                let is_read = apply_op(store.as_ref(), ops.get(cursor.next_idx()));
+                let ns = intended_start.elapsed().as_nanos() as u64;
+                if is_read {
+                    read_hist.record(ns);
+                    local_reads += 1;
+                } else {
+                    write_hist.record(ns);
+                    local_writes += 1;
+                }
+                local_ops += 1;
+
+                i += 1;
+                intended_start = measure_start + interval.mul_f64(i as f64);
             }
         }
-        local_ops += 1;
-        
-        op_idx += 1;
-        if op_idx >= end_idx {
-            op_idx = start_idx;
-        }
     }
-    
+
     ops_counter.fetch_add(local_ops, Ordering::Relaxed);
     WorkerResults {
         read_hist,
         write_hist,
         read_count: local_reads,
         write_count: local_writes,
-        total_ops: local_ops,
     }
 }
 
@@ -350,51 +480,81 @@ fn get_rss_bytes() -> Option<u64> {
     None
 }
 
-fn main() {
-    let args = Args::parse();
-    
-    println!("Loading initial dataset from {}...", args.initial);
-    let maps = Arc::new(load_initial(&args.initial, args.shards));
-    let n_initial = maps.iter().map(|m| m.read().unwrap().len()).sum();
-    println!("Loaded {} entries into {} shards", n_initial, args.shards);
-    
-    println!("Loading operations from {}...", args.ops);
-    let all_ops = Arc::new(load_ops(&args.ops));
-    println!("Loaded {} operations", all_ops.len());
-    
-    let ops_per_thread = all_ops.len() / args.threads;
-    let ops_counter = Arc::new(AtomicU64::new(0));
-    
+struct RunOutcome {
+    ops_total: u64,
+    total_reads: u64,
+    total_writes: u64,
+    merged_read_hist: Histogram,
+    merged_write_hist: Histogram,
+}
+
+// Splits `total_ops` into fixed-size chunks, shuffles their order with a
+// `seed`-derived RNG, and round-robin deals the shuffled chunks across
+// `threads` workers. Every thread ends up sampling across the whole ops
+// file instead of one contiguous, and therefore key-space-local, slice.
+fn deal_shuffled_chunks(total_ops: usize, chunk_size: usize, threads: usize, seed: u64) -> Vec<Vec<(usize, usize)>> {
+    let chunk_size = chunk_size.max(1);
+    let n_chunks = total_ops.div_ceil(chunk_size);
+
+    let mut order: Vec<usize> = (0..n_chunks).collect();
+    let mut rng = workload::Rng::new(seed);
+    for i in (1..n_chunks).rev() {
+        let j = (rng.next_u64() as usize) % (i + 1);
+        order.swap(i, j);
+    }
+
+    let mut per_thread: Vec<Vec<(usize, usize)>> = vec![Vec::new(); threads];
+    for (i, chunk_idx) in order.into_iter().enumerate() {
+        let start = chunk_idx * chunk_size;
+        let end = (start + chunk_size).min(total_ops);
+        per_thread[i % threads].push((start, end));
+    }
+    // Degenerate case: fewer chunks than threads leaves some threads with no
+    // assignment; fall back to the full range so every worker still runs.
+    for chunks in per_thread.iter_mut() {
+        if chunks.is_empty() {
+            chunks.push((0, total_ops));
+        }
+    }
+    per_thread
+}
+
+// Spins up `args.threads` workers against `store`, all reading from the
+// same `all_ops` log, and merges their per-thread histograms and counters.
+fn run_workers<S: Store + 'static>(args: &Args, store: S, all_ops: Arc<OpsFile>) -> RunOutcome {
     let warmup_duration = Duration::from_secs_f64(args.warmup_s);
     let measure_duration = Duration::from_secs_f64(args.duration_s);
-    
-    println!("Starting {} threads (warmup: {:?}, measure: {:?})...", 
+
+    println!("Starting {} threads (warmup: {:?}, measure: {:?})...",
              args.threads, warmup_duration, measure_duration);
-    
+
+    let mut per_thread_chunks = deal_shuffled_chunks(all_ops.len(), args.chunk_size, args.threads, args.seed);
+
+    let ctx = Arc::new(WorkerCtx {
+        store: Arc::new(store),
+        ops: all_ops,
+        warmup_duration,
+        measure_duration,
+        ops_counter: Arc::new(AtomicU64::new(0)),
+        open_loop_rate_per_sec: args.target_ops_per_sec.map(|r| r / args.threads as f64),
+        // All threads wait here once their own warmup completes so the
+        // measurement window starts at the same instant for everyone.
+        start_barrier: Barrier::new(args.threads),
+        measure_start_once: OnceLock::new(),
+    });
+
     let mut handles = Vec::new();
-    for i in 0..args.threads {
-        let maps_clone = Arc::clone(&maps);
-        let ops_clone = Arc::clone(&all_ops);
-        let counter_clone = Arc::clone(&ops_counter);
-        let start_idx = i * ops_per_thread;
-        let end_idx = if i == args.threads - 1 {
-            all_ops.len()
-        } else {
-            (i + 1) * ops_per_thread
-        };
-        
-        let handle = thread::spawn(move || {
-            worker(maps_clone, ops_clone, start_idx, end_idx, 
-                   warmup_duration, measure_duration, counter_clone)
-        });
+    for chunks in per_thread_chunks.drain(..) {
+        let ctx_clone = Arc::clone(&ctx);
+        let handle = thread::spawn(move || worker(ctx_clone, chunks));
         handles.push(handle);
     }
-    
+
     let mut merged_read_hist = Histogram::new();
     let mut merged_write_hist = Histogram::new();
     let mut total_reads = 0u64;
     let mut total_writes = 0u64;
-    
+
     for handle in handles {
         let results = handle.join().unwrap();
         merged_read_hist.merge(&results.read_hist);
@@ -402,12 +562,96 @@ fn main() {
         total_reads += results.read_count;
         total_writes += results.write_count;
     }
-    
-    let ops_total = ops_counter.load(Ordering::Relaxed);
+
+    RunOutcome {
+        ops_total: ctx.ops_counter.load(Ordering::Relaxed),
+        total_reads,
+        total_writes,
+        merged_read_hist,
+        merged_write_hist,
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if let Some(rate) = args.target_ops_per_sec {
+        if !rate.is_finite() || rate <= 0.0 {
+            eprintln!("--target_ops_per_sec must be a finite value > 0.0, got {rate}");
+            std::process::exit(1);
+        }
+    }
+
+    let n_initial = store::count_initial(&args.initial);
+    println!("Loading initial dataset from {}...", args.initial);
+    println!("Loaded {} entries for backend {}", n_initial, args.backend.as_str());
+
+    let ops_bin_path = if args.ops == GENERATE_OPS {
+        let keys = store::load_initial_keys(&args.initial);
+        let bin_path = "data/ops.generated.bin".to_string();
+        println!(
+            "Generating {} ops ({} dist, read_pct {}, seed {}) into {}...",
+            args.ops_count, args.dist.as_str(), args.read_pct, args.seed, bin_path
+        );
+        workload::generate(&bin_path, &keys, args.ops_count, args.read_pct, args.dist, args.theta, args.seed)
+            .expect("Failed to generate ops file");
+        bin_path
+    } else if args.ops.ends_with(".bin") {
+        args.ops.clone()
+    } else {
+        let bin_path = format!("{}.bin", args.ops);
+        println!("Packing {} into binary ops format at {}...", args.ops, bin_path);
+        ops_file::pack(&args.ops, &bin_path).expect("Failed to pack ops file");
+        bin_path
+    };
+    println!("Loading operations from {}...", ops_bin_path);
+    let all_ops = Arc::new(OpsFile::open(&ops_bin_path).expect("Failed to mmap ops file"));
+    println!("Loaded {} operations", all_ops.len());
+
+    // The CLI's `--read_pct`/`--dist` only describe `--ops generate`; for a
+    // file-sourced workload, record the mix the file actually contains
+    // instead of whatever the flags happened to default to.
+    let (recorded_read_pct, recorded_dist) = if args.ops == GENERATE_OPS {
+        (args.read_pct, args.dist.as_str().to_string())
+    } else {
+        (all_ops.read_pct(), "file".to_string())
+    };
+
+    let outcome = match args.backend {
+        Backend::ShardedRwlock => run_workers(
+            &args,
+            ShardedRwLockStore::load_initial(&args.initial, args.shards),
+            Arc::clone(&all_ops),
+        ),
+        Backend::Dashmap => run_workers(
+            &args,
+            DashMapStore::load_initial(&args.initial, args.shards),
+            Arc::clone(&all_ops),
+        ),
+        Backend::Mutex => run_workers(
+            &args,
+            MutexStore::load_initial(&args.initial, args.shards),
+            Arc::clone(&all_ops),
+        ),
+        Backend::ArcSwap => run_workers(
+            &args,
+            ArcSwapStore::load_initial(&args.initial, args.shards),
+            Arc::clone(&all_ops),
+        ),
+    };
+
+    let RunOutcome {
+        ops_total,
+        total_reads,
+        total_writes,
+        merged_read_hist,
+        merged_write_hist,
+    } = outcome;
+
     let ops_per_sec = ops_total as f64 / args.duration_s;
     let reads_per_sec = total_reads as f64 / args.duration_s;
     let writes_per_sec = total_writes as f64 / args.duration_s;
-    
+
     let results = Results {
         meta: Meta {
             timestamp_utc: chrono::Utc::now().to_rfc3339(),
@@ -422,11 +666,12 @@ fn main() {
             git_commit: get_git_commit(),
             n_initial,
             ops_file: args.ops.clone(),
-            read_pct: 95, // TODO: parse from ops file
-            dist: "uniform".to_string(), // TODO: parse from args or ops file
+            read_pct: recorded_read_pct,
+            dist: recorded_dist,
+            backend: args.backend.as_str().to_string(),
             threads: args.threads,
             shards: args.shards,
-            seed: 0, // TODO: should be passed or read from metadata
+            seed: args.seed,
             warmup_s: args.warmup_s,
             duration_s: args.duration_s,
         },
@@ -437,30 +682,30 @@ fn main() {
                 let mut combined = Histogram::new();
                 combined.merge(&merged_read_hist);
                 combined.merge(&merged_write_hist);
-                combined.percentile(50.0)
+                combined.percentile(50.0) / 1000.0
             },
             latency_us_p95: {
                 let mut combined = Histogram::new();
                 combined.merge(&merged_read_hist);
                 combined.merge(&merged_write_hist);
-                combined.percentile(95.0)
+                combined.percentile(95.0) / 1000.0
             },
             latency_us_p99: {
                 let mut combined = Histogram::new();
                 combined.merge(&merged_read_hist);
                 combined.merge(&merged_write_hist);
-                combined.percentile(99.0)
+                combined.percentile(99.0) / 1000.0
             },
             reads_total: total_reads,
             reads_per_sec,
-            reads_latency_us_p50: merged_read_hist.percentile(50.0),
-            reads_latency_us_p95: merged_read_hist.percentile(95.0),
-            reads_latency_us_p99: merged_read_hist.percentile(99.0),
+            reads_latency_us_p50: merged_read_hist.percentile(50.0) / 1000.0,
+            reads_latency_us_p95: merged_read_hist.percentile(95.0) / 1000.0,
+            reads_latency_us_p99: merged_read_hist.percentile(99.0) / 1000.0,
             writes_total: total_writes,
             writes_per_sec,
-            writes_latency_us_p50: merged_write_hist.percentile(50.0),
-            writes_latency_us_p95: merged_write_hist.percentile(95.0),
-            writes_latency_us_p99: merged_write_hist.percentile(99.0),
+            writes_latency_us_p50: merged_write_hist.percentile(50.0) / 1000.0,
+            writes_latency_us_p95: merged_write_hist.percentile(95.0) / 1000.0,
+            writes_latency_us_p99: merged_write_hist.percentile(99.0) / 1000.0,
             rss_bytes: get_rss_bytes(),
         },
     };
@@ -473,8 +718,8 @@ fn main() {
     
     // Append CSV
     let csv_path = "results/results.csv";
-    let csv_header = "timestamp_utc,language,language_version,git_commit,os,arch,cpu_cores,n_initial,read_pct,dist,threads,shards,seed,warmup_s,duration_s,ops_total,ops_per_sec,latency_us_p50,latency_us_p95,latency_us_p99,rss_bytes\n";
-    let csv_line = format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{:.2},{:.2},{:.2},{:.2},{}\n",
+    let csv_header = "timestamp_utc,language,language_version,git_commit,os,arch,cpu_cores,n_initial,read_pct,dist,backend,threads,shards,seed,warmup_s,duration_s,ops_total,ops_per_sec,latency_us_p50,latency_us_p95,latency_us_p99,rss_bytes\n";
+    let csv_line = format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{:.2},{:.2},{:.2},{:.2},{}\n",
         results.meta.timestamp_utc,
         results.config.language,
         results.config.language_version,
@@ -485,6 +730,7 @@ fn main() {
         results.config.n_initial,
         results.config.read_pct,
         results.config.dist,
+        results.config.backend,
         results.config.threads,
         results.config.shards,
         results.config.seed,
@@ -510,5 +756,19 @@ fn main() {
     }
     csv_file.write_all(csv_line.as_bytes()).expect("Failed to write CSV");
     println!("CSV appended to {}", csv_path);
+
+    if let Some(baseline_path) = &args.baseline {
+        let baseline_str = std::fs::read_to_string(baseline_path)
+            .unwrap_or_else(|e| panic!("Failed to read baseline {baseline_path}: {e}"));
+        let baseline: Results = serde_json::from_str(&baseline_str)
+            .unwrap_or_else(|e| panic!("Failed to parse baseline {baseline_path}: {e}"));
+
+        println!("\nComparing against baseline {baseline_path} (fail threshold: {}%)", args.fail_threshold_pct);
+        let regressed = compare::compare_and_report(&baseline.metrics, &results.metrics, args.fail_threshold_pct);
+        if regressed {
+            eprintln!("Regression detected vs baseline");
+            std::process::exit(1);
+        }
+    }
 }
 
@@ -0,0 +1,237 @@
+// Binary, memory-mapped ops format.
+//
+// `load_ops` used to parse `ops.txt` into a `Vec<Op>` of owned `String`s, so
+// a large workload needed gigabytes of heap before measurement even started.
+// `pack` instead compiles the text log into a flat file once: a small header,
+// an arena of every distinct key/value string, and a table of
+// `(tag, key_ref, val_ref)` records pointing into that arena. `OpsFile::open`
+// mmaps the result, and `get` hands back borrowed `&str` slices straight out
+// of the mapping, so opening the file is O(1) and reading an op is
+// allocation-free.
+
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+const MAGIC: &[u8; 4] = b"BOPS";
+const VERSION: u32 = 1;
+
+const TAG_GET: u8 = 0;
+const TAG_SET: u8 = 1;
+
+// magic(4) + version(4) + op_count(8)
+const HEADER_LEN: usize = 16;
+// tag, padded to 4 bytes, + key_offset(4) + key_len(4) + val_offset(4) + val_len(4)
+const OP_RECORD_LEN: usize = 20;
+
+#[derive(Clone, Copy)]
+struct StrRef {
+    offset: u32,
+    len: u32,
+}
+
+pub enum OpRef<'a> {
+    Get(&'a str),
+    Set(&'a str, &'a str),
+}
+
+/// Accumulates ops into an arena + record table and writes them out in the
+/// binary format `OpsFile` mmaps. Both the text-log packer (`pack`, below)
+/// and the in-memory workload generator build on this so there is exactly
+/// one place that writes the on-disk layout (`OpsFile` reads it).
+pub struct OpsBuilder {
+    arena: Vec<u8>,
+    interned: HashMap<String, StrRef>,
+    records: Vec<(u8, StrRef, StrRef)>,
+}
+
+impl OpsBuilder {
+    pub fn new() -> Self {
+        Self {
+            arena: Vec::new(),
+            interned: HashMap::new(),
+            records: Vec::new(),
+        }
+    }
+
+    fn intern(&mut self, s: &str) -> StrRef {
+        if let Some(r) = self.interned.get(s) {
+            return *r;
+        }
+        let end = self.arena.len() + s.len();
+        assert!(
+            end <= u32::MAX as usize,
+            "ops arena exceeds {} bytes (u32::MAX); StrRef offsets would silently wrap",
+            u32::MAX
+        );
+        let r = StrRef {
+            offset: self.arena.len() as u32,
+            len: s.len() as u32,
+        };
+        self.arena.extend_from_slice(s.as_bytes());
+        self.interned.insert(s.to_string(), r);
+        r
+    }
+
+    pub fn push_get(&mut self, key: &str) {
+        let key = self.intern(key);
+        self.records.push((TAG_GET, key, StrRef { offset: 0, len: 0 }));
+    }
+
+    pub fn push_set(&mut self, key: &str, val: &str) {
+        let key = self.intern(key);
+        let val = self.intern(val);
+        self.records.push((TAG_SET, key, val));
+    }
+
+    pub fn write(self, bin_path: &str) -> io::Result<()> {
+        let mut out = File::create(bin_path)?;
+        out.write_all(MAGIC)?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&(self.records.len() as u64).to_le_bytes())?;
+        for (tag, key, val) in &self.records {
+            out.write_all(&[*tag, 0, 0, 0])?;
+            out.write_all(&key.offset.to_le_bytes())?;
+            out.write_all(&key.len.to_le_bytes())?;
+            out.write_all(&val.offset.to_le_bytes())?;
+            out.write_all(&val.len.to_le_bytes())?;
+        }
+        out.write_all(&self.arena)?;
+        Ok(())
+    }
+}
+
+/// Packs a text `ops.txt` (lines of `G key` or `S key value...`) into the
+/// binary format `OpsFile` mmaps.
+pub fn pack(txt_path: &str, bin_path: &str) -> io::Result<()> {
+    let reader = BufReader::new(File::open(txt_path)?);
+    let mut builder = OpsBuilder::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.is_empty() {
+            continue;
+        }
+        match parts[0] {
+            "G" if parts.len() >= 2 => builder.push_get(parts[1]),
+            "S" if parts.len() >= 3 => builder.push_set(parts[1], &parts[2..].join(" ")),
+            _ => {}
+        }
+    }
+
+    builder.write(bin_path)
+}
+
+/// A packed ops log, mmapped in full. `get` returns `&str` slices borrowed
+/// directly from the mapping.
+pub struct OpsFile {
+    mmap: Mmap,
+    op_count: usize,
+    arena_offset: usize,
+}
+
+impl OpsFile {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        assert_eq!(&mmap[0..4], MAGIC, "{path}: not a bench-rust-core ops file");
+        let version = u32::from_le_bytes(mmap[4..8].try_into().unwrap());
+        assert_eq!(version, VERSION, "{path}: unsupported ops file version {version}");
+        let op_count = u64::from_le_bytes(mmap[8..16].try_into().unwrap()) as usize;
+        let arena_offset = HEADER_LEN + op_count * OP_RECORD_LEN;
+
+        Ok(Self {
+            mmap,
+            op_count,
+            arena_offset,
+        })
+    }
+
+    // Percentage of ops in the file tagged `TAG_GET`, rounded down. Used to
+    // record the actual read/write mix of a file-sourced workload in the
+    // results `Config`, since the CLI's `--read_pct` only describes
+    // generated workloads.
+    pub fn read_pct(&self) -> usize {
+        if self.op_count == 0 {
+            return 0;
+        }
+        let mut reads = 0usize;
+        for idx in 0..self.op_count {
+            let rec_offset = HEADER_LEN + idx * OP_RECORD_LEN;
+            if self.mmap[rec_offset] == TAG_GET {
+                reads += 1;
+            }
+        }
+        reads * 100 / self.op_count
+    }
+
+    pub fn len(&self) -> usize {
+        self.op_count
+    }
+
+    pub fn get(&self, idx: usize) -> OpRef<'_> {
+        let rec_offset = HEADER_LEN + idx * OP_RECORD_LEN;
+        let rec = &self.mmap[rec_offset..rec_offset + OP_RECORD_LEN];
+        let tag = rec[0];
+        let key_offset = u32::from_le_bytes(rec[4..8].try_into().unwrap()) as usize;
+        let key_len = u32::from_le_bytes(rec[8..12].try_into().unwrap()) as usize;
+        let val_offset = u32::from_le_bytes(rec[12..16].try_into().unwrap()) as usize;
+        let val_len = u32::from_le_bytes(rec[16..20].try_into().unwrap()) as usize;
+
+        let key = self.str_at(key_offset, key_len);
+        match tag {
+            TAG_GET => OpRef::Get(key),
+            TAG_SET => OpRef::Set(key, self.str_at(val_offset, val_len)),
+            _ => panic!("corrupt ops file: unknown op tag {tag}"),
+        }
+    }
+
+    fn str_at(&self, offset: usize, len: usize) -> &str {
+        let start = self.arena_offset + offset;
+        std::str::from_utf8(&self.mmap[start..start + len]).expect("ops arena slice is not valid utf8")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_deduplicates_repeated_strings() {
+        let mut builder = OpsBuilder::new();
+        builder.intern("same-key");
+        builder.intern("same-key");
+        assert_eq!(builder.arena, b"same-key");
+    }
+
+    #[test]
+    fn pack_and_reopen_round_trips_gets_and_sets() {
+        let mut builder = OpsBuilder::new();
+        builder.push_get("abc123");
+        builder.push_set("abc123", "https://example.com");
+
+        let path = std::env::temp_dir().join(format!("bench-rust-core-test-{}.bin", std::process::id()));
+        let path = path.to_str().unwrap();
+        builder.write(path).expect("write ops file");
+
+        let ops = OpsFile::open(path).expect("open ops file");
+        assert_eq!(ops.len(), 2);
+        match ops.get(0) {
+            OpRef::Get(key) => assert_eq!(key, "abc123"),
+            OpRef::Set(..) => panic!("expected Get at index 0"),
+        }
+        match ops.get(1) {
+            OpRef::Set(key, val) => {
+                assert_eq!(key, "abc123");
+                assert_eq!(val, "https://example.com");
+            }
+            OpRef::Get(..) => panic!("expected Set at index 1"),
+        }
+        assert_eq!(ops.read_pct(), 50);
+
+        std::fs::remove_file(path).ok();
+    }
+}
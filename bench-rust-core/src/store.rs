@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::{Mutex, RwLock};
+
+use arc_swap::ArcSwap;
+use clap::ValueEnum;
+use dashmap::DashMap;
+use std::sync::Arc;
+
+// FNV-1a 64-bit hash, used by the sharded-rwlock backend to pick a shard.
+fn fnv1a64(s: &str) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 14695981039346656037;
+    const FNV_PRIME: u64 = 1099511628211;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in s.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+fn load_initial_pairs(path: &str) -> Vec<(String, String)> {
+    let file = File::open(path).expect("Failed to open initial.tsv");
+    let reader = BufReader::new(file);
+    let mut pairs = Vec::new();
+
+    for line in reader.lines() {
+        let line = line.expect("Failed to read line");
+        let parts: Vec<&str> = line.split('\t').collect();
+        if parts.len() >= 2 {
+            pairs.push((parts[0].to_string(), parts[1].to_string()));
+        }
+    }
+
+    pairs
+}
+
+// Number of entries `initial.tsv` will seed the store with, independent of
+// which backend loads them.
+pub fn count_initial(path: &str) -> usize {
+    load_initial_pairs(path).len()
+}
+
+// Just the keys from `initial.tsv`, for the generated-workload path to draw
+// from without caring which backend ultimately loads the pairs.
+pub fn load_initial_keys(path: &str) -> Vec<String> {
+    load_initial_pairs(path).into_iter().map(|(key, _)| key).collect()
+}
+
+/// Selectable key/value backend for the benchmark, so the harness can
+/// compare implementations head-to-head instead of measuring a single one.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum Backend {
+    ShardedRwlock,
+    Dashmap,
+    Mutex,
+    ArcSwap,
+}
+
+impl Backend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::ShardedRwlock => "sharded-rwlock",
+            Backend::Dashmap => "dashmap",
+            Backend::Mutex => "mutex",
+            Backend::ArcSwap => "arc-swap",
+        }
+    }
+}
+
+/// A key/value backend the benchmark can drive. `get`/`set` are called from
+/// every worker thread concurrently, so implementations must be `Send + Sync`.
+pub trait Store: Send + Sync {
+    fn get(&self, key: &str) -> Option<String>;
+    fn set(&self, key: &str, val: String);
+    fn load_initial(path: &str, shards: usize) -> Self
+    where
+        Self: Sized;
+}
+
+/// Today's default: a fixed number of shards, each an independently locked
+/// `RwLock<HashMap>`, selected by hashing the key.
+pub struct ShardedRwLockStore {
+    shards: Vec<Arc<RwLock<HashMap<String, String>>>>,
+}
+
+impl Store for ShardedRwLockStore {
+    fn get(&self, key: &str) -> Option<String> {
+        let idx = (fnv1a64(key) as usize) % self.shards.len();
+        self.shards[idx].read().unwrap().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, val: String) {
+        let idx = (fnv1a64(key) as usize) % self.shards.len();
+        self.shards[idx].write().unwrap().insert(key.to_string(), val);
+    }
+
+    fn load_initial(path: &str, shards: usize) -> Self {
+        let maps: Vec<Arc<RwLock<HashMap<String, String>>>> = (0..shards)
+            .map(|_| Arc::new(RwLock::new(HashMap::new())))
+            .collect();
+
+        for (code, url) in load_initial_pairs(path) {
+            let idx = (fnv1a64(&code) as usize) % shards;
+            maps[idx].write().unwrap().insert(code, url);
+        }
+
+        Self { shards: maps }
+    }
+}
+
+/// Single global lock, for measuring how much the sharding above actually buys.
+pub struct MutexStore {
+    map: Mutex<HashMap<String, String>>,
+}
+
+impl Store for MutexStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.map.lock().unwrap().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, val: String) {
+        self.map.lock().unwrap().insert(key.to_string(), val);
+    }
+
+    fn load_initial(path: &str, _shards: usize) -> Self {
+        let mut map = HashMap::new();
+        for (code, url) in load_initial_pairs(path) {
+            map.insert(code, url);
+        }
+        Self { map: Mutex::new(map) }
+    }
+}
+
+/// `DashMap` concurrent hash map, which locks its own internal shards.
+pub struct DashMapStore {
+    map: DashMap<String, String>,
+}
+
+impl Store for DashMapStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.map.get(key).map(|entry| entry.value().clone())
+    }
+
+    fn set(&self, key: &str, val: String) {
+        self.map.insert(key.to_string(), val);
+    }
+
+    fn load_initial(path: &str, _shards: usize) -> Self {
+        let map = DashMap::new();
+        for (code, url) in load_initial_pairs(path) {
+            map.insert(code, url);
+        }
+        Self { map }
+    }
+}
+
+/// Read-optimized snapshot variant: reads are a single atomic load with no
+/// locking, writes pay for a full copy-on-write of the map.
+pub struct ArcSwapStore {
+    snapshot: ArcSwap<HashMap<String, String>>,
+}
+
+impl Store for ArcSwapStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.snapshot.load().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, val: String) {
+        // `rcu` re-runs the closure against the latest snapshot if another
+        // thread's store wins the race, so a losing write re-applies itself
+        // instead of silently clobbering (or being clobbered by) the winner.
+        self.snapshot.rcu(|current| {
+            let mut next = HashMap::clone(current);
+            next.insert(key.to_string(), val.clone());
+            next
+        });
+    }
+
+    fn load_initial(path: &str, _shards: usize) -> Self {
+        let mut map = HashMap::new();
+        for (code, url) in load_initial_pairs(path) {
+            map.insert(code, url);
+        }
+        Self {
+            snapshot: ArcSwap::new(Arc::new(map)),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// In-memory workload generation. Synthesized ops are packed through the same
+// `OpsBuilder` the text-log path uses, so generated and file-loaded
+// workloads run through one `OpsFile` mmap regardless of source.
+
+use clap::ValueEnum;
+use std::io;
+
+use crate::ops_file::OpsBuilder;
+
+/// Key-access distribution for generated workloads.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum Dist {
+    Uniform,
+    Zipf,
+}
+
+impl Dist {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Dist::Uniform => "uniform",
+            Dist::Zipf => "zipf",
+        }
+    }
+}
+
+// splitmix64, seeded from `--seed` so a generated workload (or, via
+// `main::run_workers`, a shuffled chunk order) is reproducible run to run.
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// Precomputed cumulative Zipf distribution over `n` ranks (probability of
+// rank `i` proportional to `1/i^theta`), so sampling a key is a binary
+// search against `cumulative` rather than a fresh sum per draw.
+struct ZipfTable {
+    cumulative: Vec<f64>,
+}
+
+impl ZipfTable {
+    fn new(n: usize, theta: f64) -> Self {
+        let mut cumulative: Vec<f64> = (1..=n).map(|rank| 1.0 / (rank as f64).powf(theta)).collect();
+        let normalizer: f64 = cumulative.iter().sum();
+        let mut acc = 0.0;
+        for w in cumulative.iter_mut() {
+            acc += *w / normalizer;
+            *w = acc;
+        }
+        Self { cumulative }
+    }
+
+    fn sample(&self, u: f64) -> usize {
+        match self.cumulative.binary_search_by(|v| v.partial_cmp(&u).unwrap()) {
+            Ok(idx) | Err(idx) => idx.min(self.cumulative.len() - 1),
+        }
+    }
+}
+
+/// Synthesizes `count` operations against `keys` (read vs write chosen by
+/// `read_pct`, key chosen per `dist`) and packs them into the binary ops
+/// format at `bin_path`.
+pub fn generate(
+    bin_path: &str,
+    keys: &[String],
+    count: usize,
+    read_pct: usize,
+    dist: Dist,
+    theta: f64,
+    seed: u64,
+) -> io::Result<()> {
+    let mut rng = Rng::new(seed);
+    let zipf = match dist {
+        Dist::Zipf => Some(ZipfTable::new(keys.len(), theta)),
+        Dist::Uniform => None,
+    };
+
+    let mut builder = OpsBuilder::new();
+    for _ in 0..count {
+        let key_idx = match &zipf {
+            Some(table) => table.sample(rng.next_f64()),
+            None => (rng.next_u64() as usize) % keys.len(),
+        };
+        let key = &keys[key_idx];
+
+        if (rng.next_u64() % 100) < read_pct as u64 {
+            builder.push_get(key);
+        } else {
+            let val = format!("http://generated/{}", rng.next_u64());
+            builder.push_set(key, &val);
+        }
+    }
+
+    builder.write(bin_path)
+}